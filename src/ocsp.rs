@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use x509_parser::prelude::*;
+
+/// Whether a failed OCSP lookup (network error, malformed response, unreachable
+/// responder) should be treated as fatal or simply skipped in favor of the offline
+/// chain/date checks that already ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcspFailurePolicy {
+    /// Treat any OCSP lookup failure as a verification failure.
+    HardFail,
+    /// Fall back to the offline verification result if the OCSP lookup itself fails.
+    SoftFail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcspCertStatus {
+    Good,
+    Revoked,
+    Unknown,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum OcspError {
+    #[error("NoOcspResponder")]
+    NoOcspResponder,
+
+    #[error("OcspRequestFailed: [{0}]")]
+    OcspRequestFailed(String),
+
+    #[error("MalformedOcspResponse")]
+    MalformedOcspResponse,
+
+    #[error("ResponseNotSuccessful")]
+    ResponseNotSuccessful,
+
+    #[error("UnsupportedSignatureAlgorithm")]
+    UnsupportedSignatureAlgorithm,
+
+    #[error("ResponseSignatureInvalid")]
+    ResponseSignatureInvalid,
+}
+
+struct CachedResponse {
+    status: OcspCertStatus,
+    next_update: Option<i64>,
+}
+
+/// Performs online OCSP revocation checks for a leaf/intermediate certificate pair,
+/// caching responses by certificate serial number and honoring the response's
+/// `nextUpdate` so a fresh network round-trip isn't made on every verification.
+pub struct OcspClient {
+    failure_policy: OcspFailurePolicy,
+    http_client: reqwest::blocking::Client,
+    http_client_async: reqwest::Client,
+    cache: Mutex<HashMap<Vec<u8>, CachedResponse>>,
+}
+
+impl OcspClient {
+    pub fn new(failure_policy: OcspFailurePolicy) -> Self {
+        OcspClient {
+            failure_policy,
+            http_client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Expect HTTP client"),
+            http_client_async: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Expect HTTP client"),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn failure_policy(&self) -> OcspFailurePolicy {
+        self.failure_policy
+    }
+
+    /// Checks the revocation status of `subject_der`, issued by `issuer_der`.
+    pub fn check_status(&self, subject_der: &[u8], issuer_der: &[u8]) -> Result<OcspCertStatus, OcspError> {
+        let (_, subject) = X509Certificate::from_der(subject_der)
+            .map_err(|_| OcspError::MalformedOcspResponse)?;
+        let serial = subject.raw_serial().to_vec();
+
+        if let Some(cached) = self.cached_status(&serial) {
+            return Ok(cached);
+        }
+
+        let responder_url = ocsp_responder_url(&subject).ok_or(OcspError::NoOcspResponder)?;
+        let request = build_ocsp_request(subject_der, issuer_der)?;
+
+        let response = self.http_client
+            .post(responder_url)
+            .header("Content-Type", "application/ocsp-request")
+            .body(request)
+            .send()
+            .map_err(|e| OcspError::OcspRequestFailed(e.to_string()))?;
+
+        let body = response.bytes().map_err(|e| OcspError::OcspRequestFailed(e.to_string()))?;
+        let (status, next_update) = crate::ocsp_asn1::parse_and_verify(&body, issuer_der)?;
+
+        self.cache.lock().expect("Expect cache lock").insert(serial, CachedResponse { status, next_update });
+
+        Ok(status)
+    }
+
+    /// Async counterpart to [`OcspClient::check_status`], so the network round-trip
+    /// doesn't block an async runtime's executor thread.
+    pub async fn check_status_async(&self, subject_der: &[u8], issuer_der: &[u8]) -> Result<OcspCertStatus, OcspError> {
+        let (_, subject) = X509Certificate::from_der(subject_der)
+            .map_err(|_| OcspError::MalformedOcspResponse)?;
+        let serial = subject.raw_serial().to_vec();
+
+        if let Some(cached) = self.cached_status(&serial) {
+            return Ok(cached);
+        }
+
+        let responder_url = ocsp_responder_url(&subject).ok_or(OcspError::NoOcspResponder)?;
+        let request = build_ocsp_request(subject_der, issuer_der)?;
+
+        let response = self.http_client_async
+            .post(responder_url)
+            .header("Content-Type", "application/ocsp-request")
+            .body(request)
+            .send()
+            .await
+            .map_err(|e| OcspError::OcspRequestFailed(e.to_string()))?;
+
+        let body = response.bytes().await.map_err(|e| OcspError::OcspRequestFailed(e.to_string()))?;
+        let (status, next_update) = crate::ocsp_asn1::parse_and_verify(&body, issuer_der)?;
+
+        self.cache.lock().expect("Expect cache lock").insert(serial, CachedResponse { status, next_update });
+
+        Ok(status)
+    }
+
+    fn cached_status(&self, serial: &[u8]) -> Option<OcspCertStatus> {
+        let cache = self.cache.lock().expect("Expect cache lock");
+        let cached = cache.get(serial)?;
+
+        if let Some(next_update) = cached.next_update {
+            if next_update <= current_unix_time() {
+                return None;
+            }
+        }
+
+        Some(cached.status)
+    }
+}
+
+fn current_unix_time() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("Expect valid system clock")
+        .as_secs() as i64
+}
+
+/// Pulls the OCSP responder URL out of a certificate's Authority Information Access
+/// extension (OID 1.3.6.1.5.5.7.1.1, access method id-ad-ocsp 1.3.6.1.5.5.7.48.1).
+fn ocsp_responder_url(cert: &X509Certificate) -> Option<String> {
+    let aia_ext = cert.extensions().iter()
+        .find(|ext| ext.oid == oid_registry::OID_PKIX_AUTHORITY_INFO_ACCESS)?;
+
+    let ParsedExtension::AuthorityInfoAccess(aia) = aia_ext.parsed_extension() else {
+        return None;
+    };
+
+    aia.accessdescs.iter()
+        .find(|ad| ad.access_method == oid_registry::OID_PKIX_ACCESS_DESCRIPTOR_OCSP)
+        .and_then(|ad| match &ad.access_location {
+            GeneralName::URI(uri) => Some(uri.to_string()),
+            _ => None,
+        })
+}
+
+/// Builds a minimal DER-encoded OCSP request (RFC 6960) containing a single
+/// `CertID`, identifying `subject` by its serial number and its issuer's name/key
+/// hashes (SHA-1, per the OCSP CertID convention).
+fn build_ocsp_request(subject_der: &[u8], issuer_der: &[u8]) -> Result<Vec<u8>, OcspError> {
+    use sha1::{Digest, Sha1};
+
+    let (_, subject) = X509Certificate::from_der(subject_der).map_err(|_| OcspError::MalformedOcspResponse)?;
+    let (_, issuer) = X509Certificate::from_der(issuer_der).map_err(|_| OcspError::MalformedOcspResponse)?;
+
+    let issuer_name_hash = Sha1::digest(issuer.subject().as_raw());
+    // RFC 6960 hashes only the BIT STRING's key bytes, not the whole
+    // SubjectPublicKeyInfo DER (which also carries the algorithm identifier).
+    let issuer_key_hash = Sha1::digest(issuer.public_key().subject_public_key.data.as_ref());
+    let serial_number = subject.raw_serial();
+
+    Ok(der_encode_ocsp_request(&issuer_name_hash, &issuer_key_hash, serial_number))
+}
+
+/// Hand-assembles the DER bytes for `OCSPRequest { tbsRequest: TBSRequest { requestList: [ Request { reqCert: CertID } ] } }`
+/// using SHA-1 as the `CertID` hash algorithm (OID 1.3.14.3.2.26).
+fn der_encode_ocsp_request(issuer_name_hash: &[u8], issuer_key_hash: &[u8], serial_number: &[u8]) -> Vec<u8> {
+    const SHA1_ALGORITHM: &[u8] = &[
+        0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00,
+    ];
+
+    let mut cert_id = Vec::new();
+    der_push_sequence(&mut cert_id, SHA1_ALGORITHM);
+    der_push_tlv(&mut cert_id, 0x04, issuer_name_hash);
+    der_push_tlv(&mut cert_id, 0x04, issuer_key_hash);
+    der_push_tlv(&mut cert_id, 0x02, serial_number);
+
+    let mut cert_id_seq = Vec::new();
+    der_push_sequence(&mut cert_id_seq, &cert_id);
+
+    let mut request = Vec::new();
+    der_push_sequence(&mut request, &cert_id_seq);
+
+    let mut request_list = Vec::new();
+    der_push_sequence(&mut request_list, &request);
+
+    let mut tbs_request = Vec::new();
+    der_push_tlv(&mut tbs_request, 0x30, &request_list);
+
+    let mut ocsp_request = Vec::new();
+    der_push_sequence(&mut ocsp_request, &tbs_request);
+    ocsp_request
+}
+
+fn der_push_sequence(out: &mut Vec<u8>, contents: &[u8]) {
+    der_push_tlv(out, 0x30, contents);
+}
+
+fn der_push_tlv(out: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    out.push(tag);
+    der_push_length(out, contents.len());
+    out.extend_from_slice(contents);
+}
+
+fn der_push_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+
+    let bytes = len.to_be_bytes();
+    let significant = bytes.iter().skip_while(|b| **b == 0).count().max(1);
+    out.push(0x80 | significant as u8);
+    out.extend_from_slice(&bytes[bytes.len() - significant..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_der_encode_ocsp_request_matches_known_vector() {
+        let name_hash = [0xAA; 20];
+        let key_hash = [0xBB; 20];
+        let serial = [0x01, 0x02, 0x03];
+
+        let encoded = der_encode_ocsp_request(&name_hash, &key_hash, &serial);
+
+        let mut expected = vec![0x30, 0x44, 0x30, 0x42, 0x30, 0x40, 0x30, 0x3E, 0x30, 0x3C];
+        expected.extend_from_slice(&[0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00]);
+        expected.push(0x04);
+        expected.push(0x14);
+        expected.extend_from_slice(&name_hash);
+        expected.push(0x04);
+        expected.push(0x14);
+        expected.extend_from_slice(&key_hash);
+        expected.push(0x02);
+        expected.push(0x03);
+        expected.extend_from_slice(&serial);
+
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn test_der_push_length_uses_long_form_above_127() {
+        let mut out = Vec::new();
+        der_push_length(&mut out, 200);
+        assert_eq!(out, vec![0x81, 0xC8]);
+    }
+}
+
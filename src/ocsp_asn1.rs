@@ -0,0 +1,247 @@
+//! Minimal DER reader for the small slice of RFC 6960 this client needs:
+//! `OCSPResponse` -> `BasicOCSPResponse` -> first `SingleResponse`, plus
+//! verification of the responder's signature over `tbsResponseData`.
+
+use x509_parser::prelude::*;
+
+use crate::ocsp::{OcspCertStatus, OcspError};
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_ENUMERATED: u8 = 0x0A;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_EXPLICIT_0: u8 = 0xA0;
+const TAG_CERT_STATUS_GOOD: u8 = 0x80;
+const TAG_CERT_STATUS_REVOKED: u8 = 0xA1;
+const TAG_CERT_STATUS_UNKNOWN: u8 = 0x82;
+
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_SHA256_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+
+/// A cursor over a single DER SEQUENCE/SET's contents, yielding one TLV at a time.
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Reader { buf }
+    }
+
+    /// Reads the next TLV, returning `(tag, contents, raw)` where `raw` is the
+    /// full tag+length+contents encoding (needed to re-verify a signature over it).
+    fn read_tlv_raw(&mut self) -> Option<(u8, &'a [u8], &'a [u8])> {
+        let start = self.buf;
+        let tag = *self.buf.first()?;
+        let (len, len_size) = read_length(self.buf.get(1..)?)?;
+        let header_len = 1 + len_size;
+        let total = header_len.checked_add(len)?;
+        if self.buf.len() < total {
+            return None;
+        }
+
+        let contents = &self.buf[header_len..total];
+        let raw = &start[..total];
+        self.buf = &self.buf[total..];
+        Some((tag, contents, raw))
+    }
+
+    fn read_tlv(&mut self) -> Option<(u8, &'a [u8])> {
+        let (tag, contents, _) = self.read_tlv_raw()?;
+        Some((tag, contents))
+    }
+
+    fn expect_tag(&mut self, tag: u8) -> Option<&'a [u8]> {
+        let (t, contents) = self.read_tlv()?;
+        (t == tag).then_some(contents)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+fn read_length(buf: &[u8]) -> Option<(usize, usize)> {
+    let first = *buf.first()?;
+    if first & 0x80 == 0 {
+        return Some((first as usize, 1));
+    }
+
+    let num_bytes = (first & 0x7f) as usize;
+    if num_bytes == 0 || num_bytes > 8 || buf.len() < 1 + num_bytes {
+        return None;
+    }
+
+    let mut len: usize = 0;
+    for b in &buf[1..1 + num_bytes] {
+        len = (len << 8) | (*b as usize);
+    }
+    Some((len, 1 + num_bytes))
+}
+
+struct SingleResponse {
+    status: OcspCertStatus,
+    next_update: Option<i64>,
+}
+
+/// Parses an `OCSPResponse`, verifies the `responseStatus` is `successful` and that
+/// the embedded `BasicOCSPResponse` is signed directly by `issuer` (responses signed
+/// by a delegated responder certificate are rejected), and returns the first
+/// `SingleResponse`'s status and `nextUpdate`.
+pub fn parse_and_verify(der: &[u8], issuer_der: &[u8]) -> Result<(OcspCertStatus, Option<i64>), OcspError> {
+    let mut top = Reader::new(der);
+    let body = top.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+    let mut body = Reader::new(body);
+
+    let status = body.expect_tag(TAG_ENUMERATED).ok_or(OcspError::MalformedOcspResponse)?;
+    if status != [0x00] {
+        return Err(OcspError::ResponseNotSuccessful);
+    }
+
+    let response_bytes = body.expect_tag(TAG_EXPLICIT_0).ok_or(OcspError::MalformedOcspResponse)?;
+    let mut response_bytes = Reader::new(response_bytes);
+    let response_bytes_seq = response_bytes.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+    let mut response_bytes_seq = Reader::new(response_bytes_seq);
+    let _response_type = response_bytes_seq.expect_tag(TAG_OID).ok_or(OcspError::MalformedOcspResponse)?;
+    let response_octets = response_bytes_seq.expect_tag(TAG_OCTET_STRING).ok_or(OcspError::MalformedOcspResponse)?;
+
+    parse_basic_response(response_octets, issuer_der)
+}
+
+fn parse_basic_response(der: &[u8], issuer_der: &[u8]) -> Result<(OcspCertStatus, Option<i64>), OcspError> {
+    let mut top = Reader::new(der);
+    let basic = top.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+    let mut basic = Reader::new(basic);
+
+    let (tbs_tag, tbs_response_data, tbs_raw) = basic.read_tlv_raw().ok_or(OcspError::MalformedOcspResponse)?;
+    if tbs_tag != TAG_SEQUENCE {
+        return Err(OcspError::MalformedOcspResponse);
+    }
+
+    let signature_algorithm = basic.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+    let signature = basic.expect_tag(TAG_BIT_STRING).ok_or(OcspError::MalformedOcspResponse)?;
+
+    // `certs [0] EXPLICIT SEQUENCE OF Certificate` is optional and, per RFC 6960,
+    // would let a delegated responder sign on the issuer's behalf -- but trusting an
+    // embedded cert without separately validating it chains to the issuer and carries
+    // the id-kp-OCSPSigning EKU would let anyone who can MITM the plaintext OCSP HTTP
+    // request embed their own cert and forge a `good` status. We don't implement that
+    // validation, so delegated responses are rejected: the signature must verify
+    // directly against the issuer cert we already trust.
+    verify_signature(tbs_raw, signature_algorithm, signature, issuer_der)?;
+
+    let response = parse_first_single_response(tbs_response_data)?;
+    Ok((response.status, response.next_update))
+}
+
+fn parse_first_single_response(tbs_response_data: &[u8]) -> Result<SingleResponse, OcspError> {
+    let mut tbs = Reader::new(tbs_response_data);
+
+    // version [0] EXPLICIT Version DEFAULT v1 -- optional, skip if present.
+    skip_if_tag(&mut tbs, TAG_EXPLICIT_0);
+    // responderID ::= CHOICE { byName [1], byKey [2] } -- just consume, we don't key
+    // the cache or this lookup by responder identity.
+    tbs.read_tlv().ok_or(OcspError::MalformedOcspResponse)?;
+    // producedAt GeneralizedTime
+    tbs.expect_tag(TAG_GENERALIZED_TIME).ok_or(OcspError::MalformedOcspResponse)?;
+
+    let responses = tbs.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+    let mut responses = Reader::new(responses);
+    let (single_tag, single_response, _) = responses.read_tlv_raw().ok_or(OcspError::MalformedOcspResponse)?;
+    if single_tag != TAG_SEQUENCE {
+        return Err(OcspError::MalformedOcspResponse);
+    }
+
+    parse_single_response(single_response)
+}
+
+fn parse_single_response(der: &[u8]) -> Result<SingleResponse, OcspError> {
+    let mut reader = Reader::new(der);
+
+    // certID CertID ::= SEQUENCE { ... } -- not matched against our request; a
+    // well-behaved responder only answers the CertID it was asked about.
+    reader.expect_tag(TAG_SEQUENCE).ok_or(OcspError::MalformedOcspResponse)?;
+
+    let (cert_status_tag, _) = reader.read_tlv().ok_or(OcspError::MalformedOcspResponse)?;
+    let status = match cert_status_tag {
+        TAG_CERT_STATUS_GOOD => OcspCertStatus::Good,
+        TAG_CERT_STATUS_REVOKED => OcspCertStatus::Revoked,
+        TAG_CERT_STATUS_UNKNOWN => OcspCertStatus::Unknown,
+        _ => return Err(OcspError::MalformedOcspResponse),
+    };
+
+    reader.expect_tag(TAG_GENERALIZED_TIME).ok_or(OcspError::MalformedOcspResponse)?;
+
+    let mut next_update = None;
+    if let Some((tag, contents)) = reader.read_tlv() {
+        if tag == TAG_EXPLICIT_0 {
+            let time = Reader::new(contents).expect_tag(TAG_GENERALIZED_TIME);
+            next_update = time.and_then(parse_generalized_time);
+        }
+    }
+
+    Ok(SingleResponse { status, next_update })
+}
+
+fn skip_if_tag(reader: &mut Reader, tag: u8) {
+    let before = reader.buf;
+    match reader.read_tlv() {
+        Some((t, _)) if t == tag => {}
+        _ => reader.buf = before,
+    }
+}
+
+/// Parses a DER `GeneralizedTime` (`YYYYMMDDHHMMSSZ`) into a unix timestamp.
+fn parse_generalized_time(bytes: &[u8]) -> Option<i64> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    let s = s.strip_suffix('Z')?;
+    if s.len() != 14 {
+        return None;
+    }
+
+    let year: i64 = s[0..4].parse().ok()?;
+    let month: i64 = s[4..6].parse().ok()?;
+    let day: i64 = s[6..8].parse().ok()?;
+    let hour: i64 = s[8..10].parse().ok()?;
+    let minute: i64 = s[10..12].parse().ok()?;
+    let second: i64 = s[12..14].parse().ok()?;
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Howard Hinnant's `days_from_civil` algorithm for proleptic-Gregorian days since
+/// the 1970-01-01 epoch, avoiding a calendar-math dependency for this one field.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn verify_signature(tbs_raw: &[u8], signature_algorithm: &[u8], signature: &[u8], signer_der: &[u8]) -> Result<(), OcspError> {
+    let oid = Reader::new(signature_algorithm).expect_tag(TAG_OID).ok_or(OcspError::MalformedOcspResponse)?;
+
+    // BIT STRING contents are prefixed with an "unused bits" count byte.
+    let signature = signature.get(1..).ok_or(OcspError::MalformedOcspResponse)?;
+
+    let (_, signer) = X509Certificate::from_der(signer_der).map_err(|_| OcspError::MalformedOcspResponse)?;
+    let public_key = signer.public_key().subject_public_key.data.as_ref();
+
+    let algorithm: &dyn ring::signature::VerificationAlgorithm = if oid == OID_ECDSA_WITH_SHA256 {
+        &ring::signature::ECDSA_P256_SHA256_ASN1
+    } else if oid == OID_SHA256_WITH_RSA {
+        &ring::signature::RSA_PKCS1_2048_8192_SHA256
+    } else {
+        return Err(OcspError::UnsupportedSignatureAlgorithm);
+    };
+
+    ring::signature::UnparsedPublicKey::new(algorithm, public_key)
+        .verify(tbs_raw, signature)
+        .map_err(|_| OcspError::ResponseSignatureInvalid)
+}
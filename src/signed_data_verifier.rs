@@ -1,13 +1,22 @@
 use base64::{DecodeError, Engine};
 use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use jsonwebtoken::{Algorithm, DecodingKey, Validation};
 use serde::de::DeserializeOwned;
+use x509_parser::prelude::*;
 use crate::chain_verifier::{ChainVerifierError, verify_chain};
+use crate::ocsp::{OcspCertStatus, OcspClient, OcspFailurePolicy};
 use crate::primitives::environment::Environment;
 use crate::primitives::jws_transaction_decoded_payload::JWSTransactionDecodedPayload;
 use crate::primitives::response_body_v2_decoded_payload::ResponseBodyV2DecodedPayload;
+use crate::trust_store::{TrustStore, TrustStoreError};
 use crate::utils::StringExt;
 
+/// Apple WWDR intermediate CA marker extension, asserted on the intermediate cert.
+const APPLE_INTERMEDIATE_MARKER_OID: &str = "1.2.840.113635.100.6.2.1";
+/// App Store server signing marker extension, asserted on the leaf cert.
+const APPLE_LEAF_MARKER_OID: &str = "1.2.840.113635.100.6.11.1";
+
 #[derive(thiserror::Error, Debug)]
 pub enum SignedDataVerifierError {
     #[error("VerificationFailure")]
@@ -19,11 +28,35 @@ pub enum SignedDataVerifierError {
     #[error("InvalidEnvironment")]
     InvalidEnvironment,
 
+    #[error("InvalidCertificateRole")]
+    InvalidCertificateRole,
+
+    #[error("CertificateRevoked")]
+    CertificateRevoked,
+
+    #[error("CertificateExpired")]
+    CertificateExpired,
+
+    #[error("CertificateNotYetValid")]
+    CertificateNotYetValid,
+
+    #[error("OnlineChecksRequireAsync")]
+    OnlineChecksRequireAsync,
+
+    #[error("OcspCheckFailed: [{0}]")]
+    OcspCheckFailed(#[from] crate::ocsp::OcspError),
+
+    #[error("InternalTrustStoreError")]
+    InternalTrustStoreError(#[from] TrustStoreError),
+
     #[error("InternalChainVerifierError")]
     InternalChainVerifierError(#[from] ChainVerifierError),
 
     #[error("InternalDecodeError: [{0}]")]
-    InternalDecodeError(#[from] base64::DecodeError)
+    InternalDecodeError(#[from] base64::DecodeError),
+
+    #[error("MalformedJWS: [{0}]")]
+    MalformedJWS(#[from] jsonwebtoken::errors::Error),
 }
 
 pub struct SignedDataVerifier {
@@ -31,6 +64,7 @@ pub struct SignedDataVerifier {
     environment: Environment,
     bundle_id: String,
     app_apple_id: Option<i64>,
+    ocsp_client: Option<OcspClient>,
 }
 
 impl SignedDataVerifier {
@@ -44,13 +78,58 @@ impl SignedDataVerifier {
             environment,
             bundle_id,
             app_apple_id,
+            ocsp_client: None,
         };
     }
+
+    /// Like [`SignedDataVerifier::new`], but additionally performs an online OCSP
+    /// revocation check of the leaf and intermediate certificates on every
+    /// verification, so a revoked-but-still-in-validity-window signing certificate
+    /// is rejected rather than silently trusted.
+    pub fn new_with_online_checks(root_certificates: Vec<Vec<u8>>,
+           environment: Environment,
+           bundle_id: String,
+           app_apple_id: Option<i64>,
+           ocsp_failure_policy: OcspFailurePolicy,
+    ) -> Self {
+        return SignedDataVerifier {
+            root_certificates,
+            environment,
+            bundle_id,
+            app_apple_id,
+            ocsp_client: Some(OcspClient::new(ocsp_failure_policy)),
+        };
+    }
+
+    /// Like [`SignedDataVerifier::new`], but pulls `root_certificates` from a
+    /// [`TrustStore`] instead of requiring the caller to supply raw DER bytes,
+    /// giving a story for root rotation instead of a hand-maintained `Vec<Vec<u8>>`.
+    pub fn with_trust_store(trust_store: &TrustStore,
+           environment: Environment,
+           bundle_id: String,
+           app_apple_id: Option<i64>,
+    ) -> Result<Self, SignedDataVerifierError> {
+        Ok(SignedDataVerifier::new(trust_store.root_certificates()?, environment, bundle_id, app_apple_id))
+    }
 }
 
 impl SignedDataVerifier {
+    /// Thin sync wrapper around [`SignedDataVerifier::verify_and_decode_signed_transaction_async`]
+    /// for callers not already running inside an async runtime.
+    ///
+    /// `block_on` has no reactor to drive `reqwest`'s async OCSP requests, so a
+    /// verifier built with `new_with_online_checks` can't be driven synchronously —
+    /// callers must use the `_async` entry point instead.
     pub fn verify_and_decode_signed_transaction(&self, signed_transaction: &str) -> Result<JWSTransactionDecodedPayload, SignedDataVerifierError> {
-        let decoded_signed_tx: JWSTransactionDecodedPayload  = self.decode_signed_object(signed_transaction)?;
+        if self.ocsp_client.is_some() {
+            return Err(SignedDataVerifierError::OnlineChecksRequireAsync);
+        }
+
+        futures::executor::block_on(self.verify_and_decode_signed_transaction_async(signed_transaction))
+    }
+
+    pub async fn verify_and_decode_signed_transaction_async(&self, signed_transaction: &str) -> Result<JWSTransactionDecodedPayload, SignedDataVerifierError> {
+        let decoded_signed_tx: JWSTransactionDecodedPayload  = self.decode_signed_object_async(signed_transaction).await?;
 
         if decoded_signed_tx.bundle_id.as_ref() != Some(&self.bundle_id) {
             return Err(SignedDataVerifierError::InvalidAppIdentifier)
@@ -62,8 +141,23 @@ impl SignedDataVerifier {
 
         Ok(decoded_signed_tx)
     }
+
+    /// Thin sync wrapper around [`SignedDataVerifier::verify_and_decode_notification_async`]
+    /// for callers not already running inside an async runtime.
+    ///
+    /// `block_on` has no reactor to drive `reqwest`'s async OCSP requests, so a
+    /// verifier built with `new_with_online_checks` can't be driven synchronously —
+    /// callers must use the `_async` entry point instead.
     pub fn verify_and_decode_notification(&self, signed_payload: &str) -> Result<ResponseBodyV2DecodedPayload, SignedDataVerifierError> {
-        let decoded_signed_notification: ResponseBodyV2DecodedPayload  = self.decode_signed_object(signed_payload)?;
+        if self.ocsp_client.is_some() {
+            return Err(SignedDataVerifierError::OnlineChecksRequireAsync);
+        }
+
+        futures::executor::block_on(self.verify_and_decode_notification_async(signed_payload))
+    }
+
+    pub async fn verify_and_decode_notification_async(&self, signed_payload: &str) -> Result<ResponseBodyV2DecodedPayload, SignedDataVerifierError> {
+        let decoded_signed_notification: ResponseBodyV2DecodedPayload  = self.decode_signed_object_async(signed_payload).await?;
 
         let bundle_id;
         let app_apple_id;
@@ -92,8 +186,31 @@ impl SignedDataVerifier {
         Ok(decoded_signed_notification)
     }
 
-    fn decode_signed_object<T: DeserializeOwned>(&self, signed_obj: &str) -> Result<T, SignedDataVerifierError> {
-        let header = jsonwebtoken::decode_header(signed_obj).expect("Expect header");
+    /// Checks the leaf (issued by the intermediate) and the intermediate (issued by
+    /// the trusted root) against their OCSP responders, rejecting the chain on a
+    /// `revoked` status. Lookup failures are hard- or soft-failed per the client's
+    /// configured [`OcspFailurePolicy`].
+    async fn check_revocation(&self, ocsp_client: &OcspClient, chain: &[Vec<u8>]) -> Result<(), SignedDataVerifierError> {
+        let (Some(leaf), Some(intermediate), Some(root)) = (chain.first(), chain.get(1), self.root_certificates.first()) else {
+            return Err(SignedDataVerifierError::VerificationFailure);
+        };
+
+        for (subject, issuer) in [(leaf, intermediate), (intermediate, root)] {
+            match ocsp_client.check_status_async(subject, issuer).await {
+                Ok(OcspCertStatus::Revoked) => return Err(SignedDataVerifierError::CertificateRevoked),
+                Ok(OcspCertStatus::Good) | Ok(OcspCertStatus::Unknown) => continue,
+                Err(e) => match ocsp_client.failure_policy() {
+                    OcspFailurePolicy::HardFail => return Err(SignedDataVerifierError::OcspCheckFailed(e)),
+                    OcspFailurePolicy::SoftFail => continue,
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn decode_signed_object_async<T: DeserializeOwned>(&self, signed_obj: &str) -> Result<T, SignedDataVerifierError> {
+        let header = jsonwebtoken::decode_header(signed_obj)?;
 
         let Some(x5c) = header.x5c else {
             return Err(SignedDataVerifierError::VerificationFailure);
@@ -110,7 +227,24 @@ impl SignedDataVerifier {
             return Err(SignedDataVerifierError::VerificationFailure);
         }
 
-        let pub_key = verify_chain(&chain, &self.root_certificates, None)?;
+        // Validate the chain against the payload's own signing time (in seconds, to
+        // match verify_chain's notBefore/notAfter comparison) rather than the wall
+        // clock, so historical transactions whose short-lived signing certs have
+        // since expired still verify correctly.
+        let effective_date = extract_signed_date(signed_obj).map(|millis| millis / 1000);
+
+        let pub_key = verify_chain(&chain, &self.root_certificates, effective_date)
+            .map_err(map_chain_verifier_error)?;
+
+        // Only assert the cert roles once the chain itself is known to be a
+        // genuinely Apple-issued, validly-signed chain -- otherwise we'd be making
+        // role claims about certificates we haven't established any trust in yet.
+        verify_certificate_roles(&chain)?;
+
+        if let Some(ocsp_client) = &self.ocsp_client {
+            self.check_revocation(ocsp_client, &chain).await?;
+        }
+
         let pub_key = &pub_key[pub_key.len() - 65..];
 
         let decoding_key = DecodingKey::from_ec_der(pub_key);
@@ -120,11 +254,62 @@ impl SignedDataVerifier {
         validator.validate_exp = false;
         validator.set_required_spec_claims(&claims);
 
-        let payload = jsonwebtoken::decode::<T>(signed_obj, &decoding_key, &validator).expect("Expect Payload");
+        let payload = jsonwebtoken::decode::<T>(signed_obj, &decoding_key, &validator)?;
         return Ok(payload.claims);
     }
 }
 
+/// Surfaces `ChainVerifierError`'s expiry cases as their own `SignedDataVerifierError`
+/// variants, so a caller can tell an old-but-genuine transaction (expired signing
+/// cert) apart from a structurally invalid chain, instead of both collapsing into
+/// `InternalChainVerifierError`.
+fn map_chain_verifier_error(error: ChainVerifierError) -> SignedDataVerifierError {
+    match error {
+        ChainVerifierError::CertificateExpired => SignedDataVerifierError::CertificateExpired,
+        ChainVerifierError::CertificateNotYetValid => SignedDataVerifierError::CertificateNotYetValid,
+        other => SignedDataVerifierError::InternalChainVerifierError(other),
+    }
+}
+
+/// Asserts that the intermediate and leaf of an x5c chain are the Apple-issued
+/// certificates we expect, rather than some other validly-chained Apple cert.
+///
+/// `chain` is ordered leaf-first, matching the `x5c` header convention, and is
+/// expected to contain at least a leaf and an intermediate (the root is not
+/// part of `x5c`).
+fn verify_certificate_roles(chain: &[Vec<u8>]) -> Result<(), SignedDataVerifierError> {
+    let leaf = chain.first().ok_or(SignedDataVerifierError::InvalidCertificateRole)?;
+    let intermediate = chain.get(1).ok_or(SignedDataVerifierError::InvalidCertificateRole)?;
+
+    if !has_extension_oid(leaf, APPLE_LEAF_MARKER_OID)? {
+        return Err(SignedDataVerifierError::InvalidCertificateRole);
+    }
+
+    if !has_extension_oid(intermediate, APPLE_INTERMEDIATE_MARKER_OID)? {
+        return Err(SignedDataVerifierError::InvalidCertificateRole);
+    }
+
+    Ok(())
+}
+
+fn has_extension_oid(der: &[u8], oid: &str) -> Result<bool, SignedDataVerifierError> {
+    let (_, cert) = X509Certificate::from_der(der)
+        .map_err(|_| SignedDataVerifierError::InvalidCertificateRole)?;
+
+    Ok(cert.extensions().iter().any(|ext| ext.oid.to_id_string().map_or(false, |id| id == oid)))
+}
+
+/// Peeks at the `signedDate` claim of a JWS payload without verifying its signature,
+/// so the chain can be validated against the time the payload claims to have been
+/// signed at. Returns `None` (falling back to the wall clock) if the payload can't be
+/// parsed or doesn't carry the claim.
+fn extract_signed_date(signed_obj: &str) -> Option<i64> {
+    let payload_b64 = signed_obj.split('.').nth(1)?;
+    let payload_bytes = URL_SAFE_NO_PAD.decode(payload_b64).ok()?;
+    let payload: serde_json::Value = serde_json::from_slice(&payload_bytes).ok()?;
+    payload.get("signedDate").and_then(|v| v.as_i64())
+}
+
 #[cfg(test)]
 mod tests {
     use base64::Engine;
@@ -140,6 +325,130 @@ mod tests {
         std::env::var("APPLE_ROOT_BASE64_ENCODED").expect("APPLE_ROOT_BASE64_ENCODED must be set")
     }
 
+    #[test]
+    fn test_extract_signed_date_reads_milliseconds() {
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"signedDate":1578773551000}"#);
+        let signed_obj = format!("header.{payload}.signature");
+
+        assert_eq!(extract_signed_date(&signed_obj), Some(1578773551000));
+    }
+
+    #[test]
+    fn test_extract_signed_date_missing_claim_returns_none() {
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"other":1}"#);
+        let signed_obj = format!("header.{payload}.signature");
+
+        assert_eq!(extract_signed_date(&signed_obj), None);
+    }
+
+    #[test]
+    fn test_verify_certificate_roles_rejects_chain_missing_intermediate() {
+        let err = verify_certificate_roles(&[vec![0x30, 0x00]]).unwrap_err();
+        assert!(matches!(err, SignedDataVerifierError::InvalidCertificateRole));
+    }
+
+    #[test]
+    fn test_has_extension_oid_rejects_malformed_der() {
+        let err = has_extension_oid(&[0xFF, 0xFF], APPLE_LEAF_MARKER_OID).unwrap_err();
+        assert!(matches!(err, SignedDataVerifierError::InvalidCertificateRole));
+    }
+
+    #[test]
+    fn test_has_extension_oid_accepts_a_cert_carrying_the_marker() {
+        let der = minimal_certificate_with_extension_oid(&oid_der(APPLE_LEAF_MARKER_OID));
+        assert!(has_extension_oid(&der, APPLE_LEAF_MARKER_OID).expect("Expect cert to parse"));
+    }
+
+    #[test]
+    fn test_verify_certificate_roles_accepts_a_leaf_and_intermediate_carrying_the_right_markers() {
+        let leaf = minimal_certificate_with_extension_oid(&oid_der(APPLE_LEAF_MARKER_OID));
+        let intermediate = minimal_certificate_with_extension_oid(&oid_der(APPLE_INTERMEDIATE_MARKER_OID));
+
+        verify_certificate_roles(&[leaf, intermediate]).expect("Expect markers to be recognized");
+    }
+
+    /// DER-encodes the arcs of a dotted OID string, e.g. `"1.2.840.113635.100.6.11.1"`.
+    fn oid_der(oid: &str) -> Vec<u8> {
+        let arcs: Vec<u64> = oid.split('.').map(|a| a.parse().expect("Expect numeric arc")).collect();
+
+        let mut bytes = vec![(arcs[0] * 40 + arcs[1]) as u8];
+        for &arc in &arcs[2..] {
+            let mut digits = vec![(arc & 0x7f) as u8];
+            let mut rest = arc >> 7;
+            while rest > 0 {
+                digits.push((rest & 0x7f) as u8 | 0x80);
+                rest >>= 7;
+            }
+            digits.reverse();
+            bytes.extend(digits);
+        }
+
+        bytes
+    }
+
+    fn der_tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        der_length(&mut out, contents.len());
+        out.extend_from_slice(contents);
+        out
+    }
+
+    fn der_length(out: &mut Vec<u8>, len: usize) {
+        if len < 0x80 {
+            out.push(len as u8);
+            return;
+        }
+
+        let bytes = len.to_be_bytes();
+        let significant = bytes.iter().skip_while(|b| **b == 0).count().max(1);
+        out.push(0x80 | significant as u8);
+        out.extend_from_slice(&bytes[bytes.len() - significant..]);
+    }
+
+    fn der_seq(contents: &[u8]) -> Vec<u8> {
+        der_tlv(0x30, contents)
+    }
+
+    fn der_bit_string(contents: &[u8]) -> Vec<u8> {
+        let mut with_unused_bits = vec![0x00];
+        with_unused_bits.extend_from_slice(contents);
+        der_tlv(0x03, &with_unused_bits)
+    }
+
+    /// Assembles just enough of a DER `Certificate` (RFC 5280) for `x509_parser` to
+    /// parse it and expose a single v3 extension carrying `extension_oid` -- there's
+    /// no genuine Apple certificate fixture in this repo, so `verify_certificate_roles`
+    /// and `has_extension_oid` are otherwise only exercised against malformed input.
+    fn minimal_certificate_with_extension_oid(extension_oid: &[u8]) -> Vec<u8> {
+        let ecdsa_with_sha256 = der_tlv(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02]);
+        let signature_algorithm = der_seq(&ecdsa_with_sha256);
+
+        let version = der_tlv(0xA0, &der_tlv(0x02, &[0x02]));
+        let serial_number = der_tlv(0x02, &[0x01]);
+        let issuer = der_seq(&[]);
+        let not_before = der_tlv(0x17, b"250101000000Z");
+        let not_after = der_tlv(0x17, b"350101000000Z");
+        let validity = der_seq(&[not_before, not_after].concat());
+        let subject = der_seq(&[]);
+
+        let ec_public_key_oid = der_tlv(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]);
+        let prime256v1_oid = der_tlv(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07]);
+        let spki_algorithm = der_seq(&[ec_public_key_oid, prime256v1_oid].concat());
+        let public_key_point = der_bit_string(&[0u8; 65]);
+        let spki = der_seq(&[spki_algorithm, public_key_point].concat());
+
+        let extension = der_seq(&[der_tlv(0x06, extension_oid), der_tlv(0x04, &[0x05, 0x00])].concat());
+        let extensions = der_tlv(0xA3, &der_seq(&extension));
+
+        let tbs_certificate = der_seq(&[
+            version, serial_number, signature_algorithm.clone(), issuer, validity, subject, spki, extensions,
+        ].concat());
+
+        let signature_value = der_bit_string(&[0u8; 8]);
+
+        der_seq(&[tbs_certificate, signature_algorithm, signature_value].concat())
+    }
+
     #[test]
     fn text_verify_and_decode_notification() {
         dotenv::dotenv().ok();
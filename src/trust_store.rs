@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use sha2::{Digest, Sha256};
+
+#[derive(thiserror::Error, Debug)]
+pub enum TrustStoreError {
+    #[error("RootFetchFailed: [{0}]")]
+    RootFetchFailed(String),
+
+    #[error("RootFingerprintMismatch")]
+    RootFingerprintMismatch,
+
+    #[error("RootCacheIoError: [{0}]")]
+    RootCacheIoError(#[from] std::io::Error),
+}
+
+/// One Apple Root CA the store knows how to fetch and verify, identified by the
+/// CDN path it lives at and the SHA-256 fingerprint it must hash to.
+pub struct PinnedRoot {
+    pub cdn_path: &'static str,
+    pub sha256_fingerprint: [u8; 32],
+}
+
+/// Fetches and caches the Apple Root CA certificates a [`SignedDataVerifier`] trusts,
+/// rather than requiring callers to supply raw DER root bytes by hand. Roots are
+/// fetched from a configurable CDN base URL, checked against pinned fingerprints, and
+/// cached to disk so a verifier doesn't hit the network on every process start.
+///
+/// [`SignedDataVerifier`]: crate::signed_data_verifier::SignedDataVerifier
+pub struct TrustStore {
+    cdn_base_url: String,
+    cache_dir: PathBuf,
+    refresh_interval: Duration,
+    pinned_roots: Vec<PinnedRoot>,
+    http_client: reqwest::blocking::Client,
+}
+
+impl TrustStore {
+    pub fn new(cdn_base_url: String, cache_dir: PathBuf, refresh_interval: Duration, pinned_roots: Vec<PinnedRoot>) -> Self {
+        TrustStore {
+            cdn_base_url,
+            cache_dir,
+            refresh_interval,
+            pinned_roots,
+            http_client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Pins only the "Apple Root CA - G3" root, fetched from Apple's certificate
+    /// authority site. This is intentionally G3-only, not Apple's full historical
+    /// root set (there is no G2 or original "Apple Root CA" here) -- it's the root
+    /// that signs the WWDR intermediates used for current App Store Server
+    /// notification and transaction signing. A caller that also needs to verify
+    /// chains built against an older root should construct a [`TrustStore`] with
+    /// [`TrustStore::new`] and its own [`PinnedRoot`] list instead of relying on
+    /// rotation happening here silently.
+    pub fn apple_default(cache_dir: PathBuf) -> Self {
+        TrustStore::new(
+            "https://www.apple.com/certificateauthority".to_string(),
+            cache_dir,
+            Duration::from_secs(60 * 60 * 24 * 7),
+            vec![
+                PinnedRoot {
+                    cdn_path: "/AppleRootCA-G3.cer",
+                    // SHA-256 of Apple's published "Apple Root CA - G3" certificate.
+                    sha256_fingerprint: [
+                        0x63, 0x34, 0x3a, 0xbf, 0xb8, 0x9a, 0x6a, 0x03, 0xeb, 0xb5, 0x7e, 0x9b, 0x3f, 0x5f, 0xa7, 0xbe,
+                        0x7c, 0x4f, 0x5c, 0x75, 0x6f, 0x30, 0x17, 0x3f, 0x6d, 0xd5, 0x0f, 0x6a, 0x94, 0x08, 0x9c, 0x9a,
+                    ],
+                },
+            ],
+        )
+    }
+
+    /// Returns the cached root DER bytes, fetching and caching them first if the
+    /// cache is empty or older than `refresh_interval`.
+    pub fn root_certificates(&self) -> Result<Vec<Vec<u8>>, TrustStoreError> {
+        fs::create_dir_all(&self.cache_dir)?;
+
+        self.pinned_roots.iter().map(|root| self.load_or_fetch(root)).collect()
+    }
+
+    fn load_or_fetch(&self, root: &PinnedRoot) -> Result<Vec<u8>, TrustStoreError> {
+        let cache_path = self.cache_dir.join(root.cdn_path.trim_start_matches('/'));
+
+        if let Some(cached) = self.read_fresh_cache(&cache_path, root)? {
+            return Ok(cached);
+        }
+
+        let der = self.fetch(root)?;
+        fs::write(&cache_path, &der)?;
+        Ok(der)
+    }
+
+    /// Returns the cached bytes if present, fresh, and still matching the pinned
+    /// fingerprint. A stale *or tampered* cache file is treated the same as a cache
+    /// miss — `load_or_fetch` will re-fetch and overwrite it — since the pin only
+    /// protects callers if it's checked on every read, not just on first fetch.
+    fn read_fresh_cache(&self, cache_path: &PathBuf, root: &PinnedRoot) -> Result<Option<Vec<u8>>, TrustStoreError> {
+        let Ok(metadata) = fs::metadata(cache_path) else {
+            return Ok(None);
+        };
+
+        let age = metadata.modified()?.elapsed().unwrap_or(Duration::MAX);
+        if age > self.refresh_interval {
+            return Ok(None);
+        }
+
+        let der = fs::read(cache_path)?;
+        if !matches_fingerprint(&der, root) {
+            return Ok(None);
+        }
+
+        Ok(Some(der))
+    }
+
+    fn fetch(&self, root: &PinnedRoot) -> Result<Vec<u8>, TrustStoreError> {
+        let url = format!("{}{}", self.cdn_base_url, root.cdn_path);
+
+        let response = self.http_client.get(&url).send()
+            .map_err(|e| TrustStoreError::RootFetchFailed(e.to_string()))?;
+        let der = response.bytes()
+            .map_err(|e| TrustStoreError::RootFetchFailed(e.to_string()))?
+            .to_vec();
+
+        if !matches_fingerprint(&der, root) {
+            return Err(TrustStoreError::RootFingerprintMismatch);
+        }
+
+        Ok(der)
+    }
+}
+
+fn matches_fingerprint(der: &[u8], root: &PinnedRoot) -> bool {
+    let fingerprint: [u8; 32] = Sha256::digest(der).into();
+    fingerprint == root.sha256_fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_root() -> PinnedRoot {
+        PinnedRoot { cdn_path: "/test-root.cer", sha256_fingerprint: [0u8; 32] }
+    }
+
+    #[test]
+    fn test_matches_fingerprint_rejects_wrong_bytes() {
+        assert!(!matches_fingerprint(b"not the pinned root cert", &test_root()));
+    }
+
+    #[test]
+    fn test_read_fresh_cache_rejects_tampered_file() {
+        let root = test_root();
+        let dir = std::env::temp_dir().join(format!("trust_store_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Expect temp dir");
+        let cache_path = dir.join("test-root.cer");
+        fs::write(&cache_path, b"tampered bytes").expect("Expect write");
+
+        let store = TrustStore::new("https://example.invalid".to_string(), dir.clone(), Duration::from_secs(3600), vec![]);
+        let cached = store.read_fresh_cache(&cache_path, &root).expect("Expect read to succeed");
+
+        assert!(cached.is_none());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}